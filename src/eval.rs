@@ -0,0 +1,337 @@
+//! A big-step evaluator for the internal (de-Bruijn-indexed) expression form
+//! produced by `parse::to_internal`.
+//!
+//! Evaluation reduces an `expr::Expr` to a runtime `Value`: closures for `Func`,
+//! tuples for `Pair`, a unit value, and existential packages for `MakeExists`.
+//! Existential packing (`MakeExists`) and unpacking (`LetExists`) are no-ops on
+//! the value level — the witness type is erased at runtime — while the usage
+//! checker is responsible for verifying that the hidden type stays properly
+//! scoped.
+//!
+//! The evaluator also gives teeth to the otherwise-inert `VarUsage::{Copy, Move}`
+//! distinction. A binding read through `Move` is *consumed*: it may not be read
+//! again on any execution path. A binding read through `Copy` is duplicated and
+//! stays available. A dedicated `check` pass runs before evaluation and reports
+//! an error when a binding is moved twice — including the case where a name is
+//! moved in one component of a `Pair` and then read in the other — so that a
+//! program is rejected statically rather than getting partway through reduction.
+
+use std::rc::Rc;
+
+use expr::{Expr, VarUsage};
+
+/// A fully-reduced runtime value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<Name> {
+    Unit,
+    /// A suspended function: its body together with the environment captured at
+    /// the point the `Func` was evaluated.
+    Closure {
+        env: Env<Name>,
+        body: Rc<Expr<Name>>,
+    },
+    Pair(Box<Value<Name>>, Box<Value<Name>>),
+    /// An existential package. The witness type is erased, so the package is just
+    /// a wrapper around the underlying value.
+    Package(Box<Value<Name>>),
+}
+
+/// A captured environment: one slot per in-scope binding, innermost last. A slot
+/// is emptied once its binding has been moved.
+pub type Env<Name> = Vec<Option<Value<Name>>>;
+
+/// Errors raised while evaluating.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    /// The affine usage check rejected the program before evaluation began.
+    Usage(UsageError),
+    /// A binding was read after having been moved away.
+    UseAfterMove { index: usize },
+    /// Application of a non-function value.
+    NotAFunction,
+    /// Destructuring of a value that is not a pair.
+    NotAPair,
+}
+
+impl From<UsageError> for EvalError {
+    fn from(err: UsageError) -> Self {
+        EvalError::Usage(err)
+    }
+}
+
+/// Errors raised by the static usage check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UsageError {
+    /// The binding at this de-Bruijn index was used (moved or copied) on a path
+    /// where it had already been consumed.
+    MovedTwice { index: usize },
+}
+
+/// Resolve a de-Bruijn index (counted from the innermost binding) to a position
+/// in a context of `len` bindings.
+fn resolve(len: usize, index: usize) -> usize {
+    len - 1 - index
+}
+
+/// Tracks, for each in-scope binding, whether it is still live (innermost last).
+struct Liveness {
+    live: Vec<bool>,
+}
+
+impl Liveness {
+    fn new() -> Self {
+        Liveness { live: Vec::new() }
+    }
+
+    fn push(&mut self) {
+        self.live.push(true);
+    }
+
+    fn pop(&mut self) {
+        self.live.pop();
+    }
+
+    /// Record a use of `index` with the given usage, consuming it on a move.
+    fn use_var(&mut self, usage: VarUsage, index: usize) -> Result<(), UsageError> {
+        let pos = resolve(self.live.len(), index);
+        if !self.live[pos] {
+            return Err(UsageError::MovedTwice { index });
+        }
+        if usage == VarUsage::Move {
+            self.live[pos] = false;
+        }
+        Ok(())
+    }
+}
+
+/// Statically check that no binding is moved more than once on any path through
+/// `expr`. Sub-expressions evaluated in sequence (a `Pair`'s components, a
+/// function and its argument) share liveness state, so a name moved in the first
+/// and read in the second is rejected.
+pub fn check<Name>(expr: &Expr<Name>) -> Result<(), UsageError> {
+    check_in(&mut Liveness::new(), expr)
+}
+
+fn check_in<Name>(live: &mut Liveness, expr: &Expr<Name>) -> Result<(), UsageError> {
+    match *expr {
+        Expr::Unit => Ok(()),
+
+        Expr::Var { usage, index } => live.use_var(usage, index),
+
+        Expr::Func { ref body, .. } => {
+            live.push();
+            let result = check_in(live, body);
+            live.pop();
+            result
+        }
+
+        Expr::App { ref callee, ref arg, .. } => {
+            check_in(live, callee)?;
+            check_in(live, arg)
+        }
+
+        Expr::Pair { ref left, ref right } => {
+            check_in(live, left)?;
+            check_in(live, right)
+        }
+
+        Expr::Let { ref val, ref body, ref names } => {
+            check_in(live, val)?;
+            for _ in 0..names.len() {
+                live.push();
+            }
+            let result = check_in(live, body);
+            for _ in 0..names.len() {
+                live.pop();
+            }
+            result
+        }
+
+        Expr::LetExists { ref val, ref body, .. } => {
+            check_in(live, val)?;
+            live.push();
+            let result = check_in(live, body);
+            live.pop();
+            result
+        }
+
+        Expr::MakeExists { ref body, .. } => check_in(live, body),
+    }
+}
+
+/// Evaluate `expr` in an empty environment, after checking its affine usage.
+pub fn eval<Name: Clone>(expr: &Expr<Name>) -> Result<Value<Name>, EvalError> {
+    // Usage is verified up front, so a double move is reported as a static
+    // `UsageError` rather than surfacing later as `UseAfterMove`.
+    check(expr)?;
+    let mut env = Vec::new();
+    eval_in(&mut env, expr)
+}
+
+fn eval_in<Name: Clone>(
+    env: &mut Env<Name>,
+    expr: &Expr<Name>,
+) -> Result<Value<Name>, EvalError> {
+    match *expr {
+        Expr::Unit => Ok(Value::Unit),
+
+        Expr::Var { usage, index } => {
+            let pos = resolve(env.len(), index);
+            match usage {
+                VarUsage::Copy => match env[pos] {
+                    Some(ref value) => Ok(value.clone()),
+                    None => Err(EvalError::UseAfterMove { index }),
+                },
+                VarUsage::Move => match env[pos].take() {
+                    Some(value) => Ok(value),
+                    None => Err(EvalError::UseAfterMove { index }),
+                },
+            }
+        }
+
+        Expr::Func { ref body, .. } => Ok(Value::Closure {
+            env: env.clone(),
+            body: Rc::new((**body).clone()),
+        }),
+
+        Expr::App { ref callee, ref arg, .. } => {
+            let callee = eval_in(env, callee)?;
+            let arg = eval_in(env, arg)?;
+            match callee {
+                Value::Closure { mut env, body } => {
+                    env.push(Some(arg));
+                    eval_in(&mut env, &body)
+                }
+                _ => Err(EvalError::NotAFunction),
+            }
+        }
+
+        Expr::Pair { ref left, ref right } => {
+            let left = eval_in(env, left)?;
+            let right = eval_in(env, right)?;
+            Ok(Value::Pair(Box::new(left), Box::new(right)))
+        }
+
+        Expr::Let { ref val, ref body, ref names } => {
+            let value = eval_in(env, val)?;
+            // A single binding takes the whole value; several destructure a
+            // right-nested pair into consecutive slots.
+            let bound = destructure(value, names.len())?;
+            let depth = env.len();
+            env.extend(bound.into_iter().map(Some));
+            let result = eval_in(env, body);
+            env.truncate(depth);
+            result
+        }
+
+        Expr::LetExists { ref val, ref body, .. } => {
+            // Unpacking is a no-op on the value: the package's contents are bound
+            // directly and the witness type is erased.
+            let value = match eval_in(env, val)? {
+                Value::Package(inner) => *inner,
+                other => other,
+            };
+            let depth = env.len();
+            env.push(Some(value));
+            let result = eval_in(env, body);
+            env.truncate(depth);
+            result
+        }
+
+        Expr::MakeExists { ref body, .. } => {
+            Ok(Value::Package(Box::new(eval_in(env, body)?)))
+        }
+    }
+}
+
+/// Split `value` into `names` bindings. One name binds the value whole; more
+/// names peel successive left components off a right-nested pair.
+fn destructure<Name>(value: Value<Name>, names: usize) -> Result<Vec<Value<Name>>, EvalError> {
+    if names <= 1 {
+        return Ok(vec![value]);
+    }
+    let mut bound = Vec::with_capacity(names);
+    let mut rest = value;
+    for _ in 0..names - 1 {
+        match rest {
+            Value::Pair(left, right) => {
+                bound.push(*left);
+                rest = *right;
+            }
+            _ => return Err(EvalError::NotAPair),
+        }
+    }
+    bound.push(rest);
+    Ok(bound)
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+    use test_utils::expr as ex;
+    use expr::VarUsage as Usage;
+
+    #[test]
+    fn eval_unit() {
+        assert_eq!(eval::<Rc<String>>(&ex::unit(0, 0)), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn let_binds_and_moves() {
+        // let x = () in move x
+        let program = ex::let_vars_named(
+            &["x"],
+            ex::unit(0, 0),
+            ex::var(Usage::Move, 1, 0, 0),
+        );
+        assert_eq!(check(&program), Ok(()));
+        assert_eq!(eval(&program), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn moving_twice_is_rejected() {
+        // let x = () in (move x, move x)
+        let program = ex::let_vars_named(
+            &["x"],
+            ex::unit(0, 0),
+            ex::pair(
+                ex::var(Usage::Move, 1, 0, 0),
+                ex::var(Usage::Move, 1, 0, 0),
+            ),
+        );
+        assert_eq!(check(&program), Err(UsageError::MovedTwice { index: 0 }));
+    }
+
+    #[test]
+    fn eval_runs_the_usage_check() {
+        // let x = () in (move x, move x) -- `eval` must reject this up front.
+        let program = ex::let_vars_named(
+            &["x"],
+            ex::unit(0, 0),
+            ex::pair(
+                ex::var(Usage::Move, 1, 0, 0),
+                ex::var(Usage::Move, 1, 0, 0),
+            ),
+        );
+        assert_eq!(
+            eval(&program),
+            Err(EvalError::Usage(UsageError::MovedTwice { index: 0 })),
+        );
+    }
+
+    #[test]
+    fn copy_then_move_is_allowed() {
+        // let x = () in (x, move x)  -- a copy followed by the final move
+        let program = ex::let_vars_named(
+            &["x"],
+            ex::unit(0, 0),
+            ex::pair(
+                ex::var(Usage::Copy, 1, 0, 0),
+                ex::var(Usage::Move, 1, 0, 0),
+            ),
+        );
+        assert_eq!(check(&program), Ok(()));
+    }
+}
@@ -0,0 +1,172 @@
+//! Rendering of parse and name-resolution errors as annotated source snippets.
+//!
+//! `lalrpop_util::ParseError` only carries raw byte offsets. This module turns
+//! such an error into a codespan-style report: the offending line with a caret
+//! underline pointing at the span, a primary label, and any number of secondary
+//! notes. Parse errors are converted by `Diagnostic::from_parse_error`; there is
+//! no conversion from `to_internal::convert_expr` failures, so any other
+//! diagnostic is assembled by hand with `Diagnostic::new` and `with_note`.
+
+use std::fmt;
+
+use lalrpop_util::ParseError;
+
+use super::lex;
+use super::syntax::Span;
+
+/// A single annotated region of the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: &str) -> Self {
+        Label { span, message: message.to_owned() }
+    }
+}
+
+/// A diagnostic to render against some source text: one primary label carrying
+/// the message, plus zero or more secondary notes shown beneath it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new(message: &str, primary: Label) -> Self {
+        Diagnostic { message: message.to_owned(), primary, secondary: Vec::new() }
+    }
+
+    /// Attach a secondary note, builder-style.
+    pub fn with_note(mut self, label: Label) -> Self {
+        self.secondary.push(label);
+        self
+    }
+
+    /// Build a diagnostic from a parse error, locating it at the byte offset(s)
+    /// the LALRPOP error reports.
+    pub fn from_parse_error(err: &ParseError<usize, lex::Token, lex::Error>) -> Self {
+        match *err {
+            ParseError::InvalidToken { location } => Diagnostic::new(
+                "invalid token",
+                Label::new(Span::new(location, location + 1), "unrecognized character"),
+            ),
+            ParseError::UnrecognizedEof { location, ref expected } => Diagnostic::new(
+                "unexpected end of input",
+                Label::new(Span::new(location, location), &expected_note(expected)),
+            ),
+            ParseError::UnrecognizedToken { ref token, ref expected } => {
+                let (start, _, end) = *token;
+                Diagnostic::new(
+                    "unexpected token",
+                    Label::new(Span::new(start, end), &expected_note(expected)),
+                )
+            }
+            ParseError::ExtraToken { ref token } => {
+                let (start, _, end) = *token;
+                Diagnostic::new(
+                    "unexpected trailing token",
+                    Label::new(Span::new(start, end), "not expected here"),
+                )
+            }
+            ParseError::User { ref error } => Diagnostic::new(
+                "lexer error",
+                Label::new(Span::new(0, 0), &format!("{:?}", error)),
+            ),
+        }
+    }
+
+    /// Render the diagnostic against `source`, producing a multi-line message
+    /// with carets underlining the primary span and dashes underlining each
+    /// secondary note.
+    pub fn render(&self, source: &str) -> String {
+        let mut out = String::new();
+        out.push_str("error: ");
+        out.push_str(&self.message);
+        out.push('\n');
+        render_label(&mut out, source, &self.primary, '^');
+        for note in &self.secondary {
+            render_label(&mut out, source, note, '-');
+        }
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {} ({})", self.message, self.primary.message)
+    }
+}
+
+fn expected_note(expected: &[String]) -> String {
+    if expected.is_empty() {
+        "unexpected token".to_owned()
+    } else {
+        format!("expected {}", expected.join(", "))
+    }
+}
+
+/// The 1-based line number and 0-based column of a byte offset, plus the byte
+/// range of the line containing it.
+fn locate(source: &str, offset: usize) -> (usize, usize, usize, usize) {
+    let offset = offset.min(source.len());
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = source[offset..].find('\n').map(|i| offset + i).unwrap_or(source.len());
+    let line_number = source[..line_start].bytes().filter(|&b| b == b'\n').count() + 1;
+    let column = offset - line_start;
+    (line_number, column, line_start, line_end)
+}
+
+fn render_label(out: &mut String, source: &str, label: &Label, caret: char) {
+    let (line_number, column, line_start, line_end) = locate(source, label.span.start);
+    let gutter = format!("{} | ", line_number);
+    out.push_str(&gutter);
+    out.push_str(&source[line_start..line_end]);
+    out.push('\n');
+
+    for _ in 0..gutter.len() + column {
+        out.push(' ');
+    }
+    let width = label.span.end.saturating_sub(label.span.start).max(1);
+    for _ in 0..width {
+        out.push(caret);
+    }
+    out.push(' ');
+    out.push_str(&label.message);
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::expr;
+
+    #[test]
+    fn node_carries_its_source_span() {
+        // `Spanned::eq` ignores the span, so structural assertions elsewhere never
+        // exercise span population; pin it down directly here.
+        let parsed = expr("foo").expect("parse");
+        assert_eq!(parsed.span, Span::new(0, 3));
+    }
+
+    #[test]
+    fn renders_caret_and_labels() {
+        let diagnostic = Diagnostic::new(
+            "unexpected token",
+            Label::new(Span::new(4, 7), "not expected here"),
+        ).with_note(Label::new(Span::new(0, 3), "while parsing this"));
+
+        let expected = concat!(
+            "error: unexpected token\n",
+            "1 | foo bar\n",
+            "        ^^^ not expected here\n",
+            "1 | foo bar\n",
+            "    --- while parsing this\n",
+        );
+        assert_eq!(diagnostic.render("foo bar"), expected);
+    }
+}
@@ -0,0 +1,330 @@
+//! The inverse of `to_internal`: rebuilding named `syntax` trees from the
+//! internal de-Bruijn-indexed representation.
+//!
+//! `to_internal::convert_expr` goes from named `syntax::Expr` to the index-based
+//! `expr::Expr`, discarding names. Any pass that works on the internal form —
+//! inference results, later optimizations — needs the opposite direction to
+//! present its output, which is what this module provides.
+//!
+//! Names are materialized through a `names::Names`-backed allocator. When an
+//! internal binder preserved its original source name we reuse it; otherwise we
+//! invent a readable one (`t0`, `x1`, …). Either way, a name that would shadow or
+//! collide with one already in scope is disambiguated through the existing
+//! `collision_id` mechanism rather than by mangling the base name, exactly as the
+//! `convert_expr` tests exercise for `Names`.
+
+use expr;
+use types;
+
+use super::names::Names;
+use super::syntax::{self, Ident, Span, Spanned};
+
+/// The context threaded through conversion: a `Names` allocator and an in-scope
+/// stack for each of the value and type namespaces, plus counters feeding the
+/// generated-name prefixes.
+pub struct Context {
+    pub var_names: Names,
+    pub type_names: Names,
+    var_scope: Vec<Ident>,
+    type_scope: Vec<Ident>,
+    var_counter: u64,
+    type_counter: u64,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Context {
+            var_names: Names::new(),
+            type_names: Names::new(),
+            var_scope: Vec::new(),
+            type_scope: Vec::new(),
+            var_counter: 0,
+            type_counter: 0,
+        }
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context::new()
+    }
+}
+
+/// Allocate the smallest-`collision_id` identifier with base name `base` that
+/// `names` will accept, using `add_name`'s collision reporting to drive the
+/// search. The base name itself is never altered.
+fn fresh_ident(names: &mut Names, base: &str) -> Ident {
+    let mut ident = Ident { name: base.to_owned(), collision_id: 0 };
+    while names.add_name(ident.clone()).is_err() {
+        ident.collision_id += 1;
+    }
+    ident
+}
+
+/// Pick the base name for a binder: its preserved source name if any, otherwise
+/// a generated `prefix`+counter name.
+fn base_name<Name: AsRef<str>>(source: &Option<Name>, prefix: &str, counter: &mut u64) -> String {
+    match *source {
+        Some(ref name) => name.as_ref().to_owned(),
+        None => {
+            let generated = format!("{}{}", prefix, *counter);
+            *counter += 1;
+            generated
+        }
+    }
+}
+
+fn synth_expr(node: syntax::ExprKind) -> syntax::Expr {
+    Spanned::new(Span::new(0, 0), node)
+}
+
+fn synth_type(node: syntax::TypeKind) -> syntax::Type {
+    Spanned::new(Span::new(0, 0), node)
+}
+
+/// Convert an internal expression back into named surface syntax.
+pub fn convert_expr<Name: AsRef<str>>(ctx: &mut Context, expr: &expr::Expr<Name>) -> syntax::Expr {
+    match *expr {
+        expr::Expr::Unit => synth_expr(syntax::ExprKind::Unit),
+
+        expr::Expr::Var { usage, index } => {
+            let ident = ctx.var_scope[ctx.var_scope.len() - 1 - index].clone();
+            synth_expr(syntax::ExprKind::Var { usage, ident })
+        }
+
+        expr::Expr::Func { ref type_params, ref arg_name, ref arg_type, ref body } => {
+            // The type binders are in scope for both the argument type and the
+            // body, so they must be pushed before either is converted and popped
+            // afterwards, as in the `LetExists`/`MakeExists` arms. Dropping them
+            // would misresolve every `Var { index }` in `arg_type`/`body`.
+            let mut converted_params = Vec::with_capacity(type_params.len());
+            for param in type_params {
+                let base = base_name(&param.name, "t", &mut ctx.type_counter);
+                let ident = fresh_ident(&mut ctx.type_names, &base);
+                ctx.type_scope.push(ident.clone());
+                converted_params.push(syntax::TypeParam { ident, kind: param.kind.clone() });
+            }
+            let arg_type = convert_type(ctx, arg_type);
+            let base = base_name(arg_name, "x", &mut ctx.var_counter);
+            let ident = fresh_ident(&mut ctx.var_names, &base);
+            ctx.var_scope.push(ident.clone());
+            let body = convert_expr(ctx, body);
+            ctx.var_scope.pop();
+            for _ in type_params {
+                ctx.type_scope.pop();
+            }
+            synth_expr(syntax::ExprKind::Func {
+                type_params: converted_params,
+                arg_name: ident,
+                arg_type: Some(arg_type),
+                body: Box::new(body),
+            })
+        }
+
+        expr::Expr::App { ref callee, ref arg, .. } => {
+            let callee = convert_expr(ctx, callee);
+            let arg = convert_expr(ctx, arg);
+            synth_expr(syntax::ExprKind::App {
+                callee: Box::new(callee),
+                type_params: None,
+                arg: Box::new(arg),
+            })
+        }
+
+        expr::Expr::Pair { ref left, ref right } => {
+            let left = convert_expr(ctx, left);
+            let right = convert_expr(ctx, right);
+            synth_expr(syntax::ExprKind::Pair {
+                left: Box::new(left),
+                right: Box::new(right),
+            })
+        }
+
+        expr::Expr::Let { ref names, ref val, ref body } => {
+            let val = convert_expr(ctx, val);
+            let idents = push_vars(ctx, names);
+            let body = convert_expr(ctx, body);
+            for _ in names {
+                ctx.var_scope.pop();
+            }
+            synth_expr(syntax::ExprKind::Let {
+                names: idents,
+                val: Box::new(val),
+                body: Box::new(body),
+            })
+        }
+
+        expr::Expr::LetExists { ref type_names, ref val_name, ref val, ref body } => {
+            let val = convert_expr(ctx, val);
+            let type_idents = push_types(ctx, type_names);
+            let base = base_name(val_name, "x", &mut ctx.var_counter);
+            let val_ident = fresh_ident(&mut ctx.var_names, &base);
+            ctx.var_scope.push(val_ident.clone());
+            let body = convert_expr(ctx, body);
+            ctx.var_scope.pop();
+            for _ in type_names {
+                ctx.type_scope.pop();
+            }
+            synth_expr(syntax::ExprKind::LetExists {
+                type_names: type_idents,
+                val_name: val_ident,
+                val: Box::new(val),
+                body: Box::new(body),
+            })
+        }
+
+        expr::Expr::MakeExists { ref params, ref type_body, ref body } => {
+            // The witnesses are bound while rendering the type body, then leave
+            // scope; the packaged value is rendered in the outer scope.
+            let mut converted_params = Vec::with_capacity(params.len());
+            for &(ref name, ref ty) in params {
+                let ty = convert_type(ctx, ty);
+                let base = base_name(name, "t", &mut ctx.type_counter);
+                let ident = fresh_ident(&mut ctx.type_names, &base);
+                ctx.type_scope.push(ident.clone());
+                converted_params.push((ident, ty));
+            }
+            let type_body = convert_type(ctx, type_body);
+            for _ in params {
+                ctx.type_scope.pop();
+            }
+            let body = convert_expr(ctx, body);
+            synth_expr(syntax::ExprKind::MakeExists {
+                params: converted_params,
+                type_body,
+                body: Box::new(body),
+            })
+        }
+    }
+}
+
+/// Allocate and push one fresh value identifier per name, returning them in
+/// binding order.
+fn push_vars<Name: AsRef<str>>(ctx: &mut Context, names: &[Option<Name>]) -> Vec<Ident> {
+    let mut idents = Vec::with_capacity(names.len());
+    for source in names {
+        let base = base_name(source, "x", &mut ctx.var_counter);
+        let ident = fresh_ident(&mut ctx.var_names, &base);
+        ctx.var_scope.push(ident.clone());
+        idents.push(ident);
+    }
+    idents
+}
+
+/// Allocate and push one fresh type identifier per name, returning them in
+/// binding order.
+fn push_types<Name: AsRef<str>>(ctx: &mut Context, names: &[Option<Name>]) -> Vec<Ident> {
+    let mut idents = Vec::with_capacity(names.len());
+    for source in names {
+        let base = base_name(source, "t", &mut ctx.type_counter);
+        let ident = fresh_ident(&mut ctx.type_names, &base);
+        ctx.type_scope.push(ident.clone());
+        idents.push(ident);
+    }
+    idents
+}
+
+/// Convert an internal type back into named surface syntax, reusing the type
+/// namespace for its bound variables.
+pub fn convert_type<Name: AsRef<str>>(ctx: &mut Context, ty: &types::Type<Name>) -> syntax::Type {
+    match *ty {
+        types::Type::Unit => synth_type(syntax::TypeKind::Unit),
+
+        types::Type::Var { index } => {
+            let ident = ctx.type_scope[ctx.type_scope.len() - 1 - index].clone();
+            synth_type(syntax::TypeKind::Var { ident })
+        }
+
+        types::Type::Func { ref arg, ref ret, .. } => {
+            let arg = convert_type(ctx, arg);
+            let ret = convert_type(ctx, ret);
+            synth_type(syntax::TypeKind::Func {
+                params: Vec::new(),
+                arg: Box::new(arg),
+                ret: Box::new(ret),
+            })
+        }
+
+        types::Type::Pair { ref left, ref right } => {
+            let left = convert_type(ctx, left);
+            let right = convert_type(ctx, right);
+            synth_type(syntax::TypeKind::Pair {
+                left: Box::new(left),
+                right: Box::new(right),
+            })
+        }
+
+        types::Type::App { ref constructor, ref param } => {
+            let constructor = convert_type(ctx, constructor);
+            let param = convert_type(ctx, param);
+            synth_type(syntax::TypeKind::App {
+                constructor: Box::new(constructor),
+                param: Box::new(param),
+            })
+        }
+
+        types::Type::Quantified { quantifier, ref param, ref body } => {
+            let base = base_name(&param.name, "t", &mut ctx.type_counter);
+            let ident = fresh_ident(&mut ctx.type_names, &base);
+            let type_param = syntax::TypeParam { ident: ident.clone(), kind: param.kind.clone() };
+            ctx.type_scope.push(ident);
+            let body = convert_type(ctx, body);
+            ctx.type_scope.pop();
+            synth_type(syntax::TypeKind::Quantified {
+                quantifier,
+                param: type_param,
+                body: Box::new(body),
+            })
+        }
+    }
+}
+
+/// Convenience entry point mirroring `to_internal`'s free-standing helper: round
+/// an expression back to syntax in a fresh, empty context.
+pub fn expr_to_syntax<Name: AsRef<str>>(expr: &expr::Expr<Name>) -> syntax::Expr {
+    convert_expr(&mut Context::new(), expr)
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+    use test_utils::expr as ex;
+    use expr::VarUsage as Usage;
+
+    fn convert(expr: &expr::Expr<Rc<String>>) -> syntax::Expr {
+        expr_to_syntax(expr)
+    }
+
+    #[test]
+    fn shadowing_names_are_disambiguated() {
+        // Two nested value binders both named `x` must not collapse onto the same
+        // identifier; the collision_id mechanism keeps them distinct while the
+        // base name stays `x`.
+        let program = ex::let_vars_named(
+            &["x"],
+            ex::unit(0, 0),
+            ex::let_vars_named(
+                &["x"],
+                ex::unit(1, 0),
+                ex::pair(
+                    ex::var(Usage::Copy, 2, 0, 0),
+                    ex::var(Usage::Copy, 2, 0, 1),
+                ),
+            ),
+        );
+        let syntax = convert(&program);
+        if let syntax::ExprKind::Let { ref names, ref body, .. } = syntax.node {
+            let outer = names[0].clone();
+            if let syntax::ExprKind::Let { names: ref inner, .. } = body.node {
+                assert_eq!(outer.name, inner[0].name);
+                assert_ne!(outer.collision_id, inner[0].collision_id);
+            } else {
+                panic!("expected nested let");
+            }
+        } else {
+            panic!("expected let");
+        }
+    }
+}
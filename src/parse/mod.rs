@@ -3,6 +3,10 @@ pub mod grammar;
 pub mod lex;
 pub mod names;
 pub mod to_internal;
+pub mod from_internal;
+pub mod tc;
+pub mod diagnostics;
+pub mod unparse;
 
 use lalrpop_util::ParseError;
 
@@ -31,10 +35,21 @@ mod test {
     use std::rc::Rc;
 
     use super::*;
-    use super::syntax::Ident;
+    use super::syntax::{Ident, Span, Spanned};
     use expr;
     use test_utils::parse_syntax::*;
 
+    // Wrap a bare `TypeKind`/`ExprKind` in a placeholder span. `Spanned` compares
+    // only the node, so these line up with the parser's real spans under
+    // `assert_eq!`.
+    fn st(node: syntax::TypeKind) -> syntax::Type {
+        Spanned::new(Span::new(0, 0), node)
+    }
+
+    fn se(node: syntax::ExprKind) -> syntax::Expr {
+        Spanned::new(Span::new(0, 0), node)
+    }
+
     fn name(s: &str) -> Result<String, ParseError<usize, lex::Token, lex::Error>> {
         grammar::RawNameParser::new().parse(lex::Lexer::from_str(s))
     }
@@ -171,14 +186,14 @@ mod test {
     }
 
     fn ty_var(s: &str) -> syntax::Type {
-        syntax::Type::Var { ident: mk_ident(s) }
+        st(syntax::TypeKind::Var { ident: mk_ident(s) })
     }
 
     #[test]
     fn test_type() {
         assert_eq!(
             type_("( // embedded whitespace \n )"),
-            Ok(syntax::Type::Unit)
+            Ok(st(syntax::TypeKind::Unit))
         );
 
         assert_eq!(type_("hello"), Ok(ty_var("hello")));
@@ -187,57 +202,58 @@ mod test {
 
         assert_eq!(
             type_("foo(bar)"),
-            Ok(syntax::Type::App {
+            Ok(st(syntax::TypeKind::App {
                 constructor: Box::new(ty_var("foo")),
                 param: Box::new(ty_var("bar")),
-            })
+            }))
         );
 
         assert_eq!(
             type_("foo(bar; baz)"),
-            Ok(syntax::Type::App {
-                constructor: Box::new(syntax::Type::App {
+            Ok(st(syntax::TypeKind::App {
+                constructor: Box::new(st(syntax::TypeKind::App {
                     constructor: Box::new(ty_var("foo")),
                     param: Box::new(ty_var("bar")),
-                }),
+                })),
                 param: Box::new(ty_var("baz")),
-            })
+            }))
         );
 
         assert_eq!(
             type_("foo(bar; baz;)"),
-            Ok(syntax::Type::App {
-                constructor: Box::new(syntax::Type::App {
+            Ok(st(syntax::TypeKind::App {
+                constructor: Box::new(st(syntax::TypeKind::App {
                     constructor: Box::new(ty_var("foo")),
                     param: Box::new(ty_var("bar")),
-                }),
+                })),
                 param: Box::new(ty_var("baz")),
-            })
+            }))
         );
 
         assert_eq!(
             type_("exists {t : *} t"),
-            Ok(syntax::Type::Exists {
+            Ok(st(syntax::TypeKind::Quantified {
+                quantifier: types::Quantifier::Exists,
                 param: syntax::TypeParam {
                     ident: mk_ident("t"),
                     kind: types::Kind::Type,
                 },
                 body: Box::new(ty_var("t")),
-            })
+            }))
         );
 
         assert_eq!(
             type_("foo -> bar"),
-            Ok(syntax::Type::Func {
+            Ok(st(syntax::TypeKind::Func {
                 params: Vec::new(),
                 arg: Box::new(ty_var("foo")),
                 ret: Box::new(ty_var("bar")),
-            })
+            }))
         );
 
         assert_eq!(
             type_("forall {t : *} t -> foo"),
-            Ok(syntax::Type::Func {
+            Ok(st(syntax::TypeKind::Func {
                 params: vec![
                     syntax::TypeParam {
                         ident: mk_ident("t"),
@@ -246,36 +262,37 @@ mod test {
                 ],
                 arg: Box::new(ty_var("t")),
                 ret: Box::new(ty_var("foo")),
-            })
+            }))
         );
 
         assert_eq!(
             type_("foo, bar, baz"),
-            Ok(syntax::Type::Pair {
+            Ok(st(syntax::TypeKind::Pair {
                 left: Box::new(ty_var("foo")),
-                right: Box::new(syntax::Type::Pair {
+                right: Box::new(st(syntax::TypeKind::Pair {
                     left: Box::new(ty_var("bar")),
                     right: Box::new(ty_var("baz")),
-                }),
-            })
+                })),
+            }))
         );
 
         assert_eq!(
             type_("foo, bar, baz,"),
-            Ok(syntax::Type::Pair {
+            Ok(st(syntax::TypeKind::Pair {
                 left: Box::new(ty_var("foo")),
-                right: Box::new(syntax::Type::Pair {
+                right: Box::new(st(syntax::TypeKind::Pair {
                     left: Box::new(ty_var("bar")),
                     right: Box::new(ty_var("baz")),
-                }),
-            })
+                })),
+            }))
         );
 
         // Full example:
 
         assert_eq!(
             type_("exists {f : (*) -> *} (Functor(f), f(T))"),
-            Ok(syntax::Type::Exists {
+            Ok(st(syntax::TypeKind::Quantified {
+                quantifier: types::Quantifier::Exists,
                 param: syntax::TypeParam {
                     ident: mk_ident("f"),
                     kind: types::Kind::Constructor {
@@ -283,39 +300,39 @@ mod test {
                         result: Rc::new(types::Kind::Type),
                     },
                 },
-                body: Box::new(syntax::Type::Pair {
-                    left: Box::new(syntax::Type::App {
+                body: Box::new(st(syntax::TypeKind::Pair {
+                    left: Box::new(st(syntax::TypeKind::App {
                         constructor: Box::new(ty_var("Functor")),
                         param: Box::new(ty_var("f")),
-                    }),
-                    right: Box::new(syntax::Type::App {
+                    })),
+                    right: Box::new(st(syntax::TypeKind::App {
                         constructor: Box::new(ty_var("f")),
                         param: Box::new(ty_var("T")),
-                    }),
-                }),
-            })
+                    })),
+                })),
+            }))
         );
     }
 
     fn ex_var(s: &str) -> syntax::Expr {
-        syntax::Expr::Var {
+        se(syntax::ExprKind::Var {
             usage: expr::VarUsage::Copy,
             ident: mk_ident(s),
-        }
+        })
     }
 
     fn ex_move_var(s: &str) -> syntax::Expr {
-        syntax::Expr::Var {
+        se(syntax::ExprKind::Var {
             usage: expr::VarUsage::Move,
             ident: mk_ident(s),
-        }
+        })
     }
 
     #[test]
     fn test_expr() {
         assert_eq!(
             expr("( // embedded whitespace \n )"),
-            Ok(syntax::Expr::Unit),
+            Ok(se(syntax::ExprKind::Unit)),
         );
 
         assert_eq!(expr("hello"), Ok(ex_var("hello")));
@@ -326,53 +343,53 @@ mod test {
 
         assert_eq!(
             expr("hello(move world)"),
-            Ok(syntax::Expr::App {
+            Ok(se(syntax::ExprKind::App {
                 callee: Box::new(ex_var("hello")),
-                type_params: Vec::new(),
+                type_params: Some(Vec::new()),
                 arg: Box::new(ex_move_var("world")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("hello{T}(move world)"),
-            Ok(syntax::Expr::App {
+            Ok(se(syntax::ExprKind::App {
                 callee: Box::new(ex_var("hello")),
-                type_params: vec![ty_var("T")],
+                type_params: Some(vec![ty_var("T")]),
                 arg: Box::new(ex_move_var("world")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("hello{T; U}(move world)"),
-            Ok(syntax::Expr::App {
+            Ok(se(syntax::ExprKind::App {
                 callee: Box::new(ex_var("hello")),
-                type_params: vec![ty_var("T"), ty_var("U")],
+                type_params: Some(vec![ty_var("T"), ty_var("U")]),
                 arg: Box::new(ex_move_var("world")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("hello{T; U;}(move world)"),
-            Ok(syntax::Expr::App {
+            Ok(se(syntax::ExprKind::App {
                 callee: Box::new(ex_var("hello")),
-                type_params: vec![ty_var("T"), ty_var("U")],
+                type_params: Some(vec![ty_var("T"), ty_var("U")]),
                 arg: Box::new(ex_move_var("world")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("func (x : T) -> move x"),
-            Ok(syntax::Expr::Func {
+            Ok(se(syntax::ExprKind::Func {
                 type_params: Vec::new(),
                 arg_name: mk_ident("x"),
-                arg_type: ty_var("T"),
+                arg_type: Some(ty_var("T")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("func {T : *} (x : T) -> move x"),
-            Ok(syntax::Expr::Func {
+            Ok(se(syntax::ExprKind::Func {
                 type_params: vec![
                     syntax::TypeParam {
                         ident: mk_ident("T"),
@@ -380,14 +397,14 @@ mod test {
                     },
                 ],
                 arg_name: mk_ident("x"),
-                arg_type: ty_var("T"),
+                arg_type: Some(ty_var("T")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("func {T : *; U : *} (x : T) -> move x"),
-            Ok(syntax::Expr::Func {
+            Ok(se(syntax::ExprKind::Func {
                 type_params: vec![
                     syntax::TypeParam {
                         ident: mk_ident("T"),
@@ -399,14 +416,14 @@ mod test {
                     },
                 ],
                 arg_name: mk_ident("x"),
-                arg_type: ty_var("T"),
+                arg_type: Some(ty_var("T")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("func {T : *; U : *;} (x : T) -> move x"),
-            Ok(syntax::Expr::Func {
+            Ok(se(syntax::ExprKind::Func {
                 type_params: vec![
                     syntax::TypeParam {
                         ident: mk_ident("T"),
@@ -418,113 +435,113 @@ mod test {
                     },
                 ],
                 arg_name: mk_ident("x"),
-                arg_type: ty_var("T"),
+                arg_type: Some(ty_var("T")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("let x = move y in move x"),
-            Ok(syntax::Expr::Let {
+            Ok(se(syntax::ExprKind::Let {
                 names: vec![mk_ident("x")],
                 val: Box::new(ex_move_var("y")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("let x, y = move z in ()"),
-            Ok(syntax::Expr::Let {
+            Ok(se(syntax::ExprKind::Let {
                 names: vec![mk_ident("x"), mk_ident("y")],
                 val: Box::new(ex_move_var("z")),
-                body: Box::new(syntax::Expr::Unit),
-            })
+                body: Box::new(se(syntax::ExprKind::Unit)),
+            }))
         );
 
         assert_eq!(
             expr("let x, y, = move z in ()"),
-            Ok(syntax::Expr::Let {
+            Ok(se(syntax::ExprKind::Let {
                 names: vec![mk_ident("x"), mk_ident("y")],
                 val: Box::new(ex_move_var("z")),
-                body: Box::new(syntax::Expr::Unit),
-            })
+                body: Box::new(se(syntax::ExprKind::Unit)),
+            }))
         );
 
         assert_eq!(
             expr("let_exists {T} x = move y in move x"),
-            Ok(syntax::Expr::LetExists {
+            Ok(se(syntax::ExprKind::LetExists {
                 type_names: vec![mk_ident("T")],
                 val_name: mk_ident("x"),
                 val: Box::new(ex_move_var("y")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("let_exists {T; U} x = move y in move x"),
-            Ok(syntax::Expr::LetExists {
+            Ok(se(syntax::ExprKind::LetExists {
                 type_names: vec![mk_ident("T"), mk_ident("U")],
                 val_name: mk_ident("x"),
                 val: Box::new(ex_move_var("y")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("let_exists {T; U;} x = move y in move x"),
-            Ok(syntax::Expr::LetExists {
+            Ok(se(syntax::ExprKind::LetExists {
                 type_names: vec![mk_ident("T"), mk_ident("U")],
                 val_name: mk_ident("x"),
                 val: Box::new(ex_move_var("y")),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("make_exists {T = Foo} T of move x"),
-            Ok(syntax::Expr::MakeExists {
+            Ok(se(syntax::ExprKind::MakeExists {
                 params: vec![(mk_ident("T"), ty_var("Foo"))],
                 type_body: ty_var("T"),
                 body: Box::new(ex_move_var("x")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("make_exists {T = Foo; U = Bar;} T -> U of move f"),
-            Ok(syntax::Expr::MakeExists {
+            Ok(se(syntax::ExprKind::MakeExists {
                 params: vec![
                     (mk_ident("T"), ty_var("Foo")),
                     (mk_ident("U"), ty_var("Bar")),
                 ],
-                type_body: syntax::Type::Func {
+                type_body: st(syntax::TypeKind::Func {
                     params: Vec::new(),
                     arg: Box::new(ty_var("T")),
                     ret: Box::new(ty_var("U")),
-                },
+                }),
                 body: Box::new(ex_move_var("f")),
-            })
+            }))
         );
 
         assert_eq!(
             expr("foo, bar, baz"),
-            Ok(syntax::Expr::Pair {
+            Ok(se(syntax::ExprKind::Pair {
                 left: Box::new(ex_var("foo")),
-                right: Box::new(syntax::Expr::Pair {
+                right: Box::new(se(syntax::ExprKind::Pair {
                     left: Box::new(ex_var("bar")),
                     right: Box::new(ex_var("baz")),
-                }),
-            })
+                })),
+            }))
         );
 
         assert_eq!(
             expr("foo, bar, baz,"),
-            Ok(syntax::Expr::Pair {
+            Ok(se(syntax::ExprKind::Pair {
                 left: Box::new(ex_var("foo")),
-                right: Box::new(syntax::Expr::Pair {
+                right: Box::new(se(syntax::ExprKind::Pair {
                     left: Box::new(ex_var("bar")),
                     right: Box::new(ex_var("baz")),
-                }),
-            })
+                })),
+            }))
         );
     }
 
@@ -1,6 +1,55 @@
 use types::{Kind, Quantifier};
 use expr::VarUsage;
 
+/// A half-open byte range `[start, end)` into the source text, used to point
+/// diagnostics back at the syntax that produced them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// The smallest span covering both `self` and `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+/// A syntax node paired with the source span it was parsed from. The LALRPOP
+/// grammar constructs these from its `@L`/`@R` location markers so that every
+/// `Type` and `Expr` node remembers where it came from.
+///
+/// Equality compares only the wrapped node: two trees are equal when they have
+/// the same shape regardless of the source positions they were parsed from, so
+/// structural assertions and round-trip tests do not have to track byte offsets.
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    pub span: Span,
+    pub node: T,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(span: Span, node: T) -> Self {
+        Spanned { span, node }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Ident {
     pub name: String,
@@ -13,8 +62,11 @@ pub struct TypeParam {
     pub kind: Kind,
 }
 
+/// A type, paired with its source span. See `TypeKind` for the variants.
+pub type Type = Spanned<TypeKind>;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Type {
+pub enum TypeKind {
     Unit,
     Var { ident: Ident },
     Quantified {
@@ -34,19 +86,28 @@ pub enum Type {
     },
 }
 
+/// An expression, paired with its source span. See `ExprKind` for the variants.
+pub type Expr = Spanned<ExprKind>;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Expr {
+pub enum ExprKind {
     Unit,
     Var { usage: VarUsage, ident: Ident },
     Func {
         type_params: Vec<TypeParam>,
         arg_name: Ident,
-        arg_type: Type,
+        // `None` when the argument type was elided and must be reconstructed by
+        // `tc`; `Some` when the user wrote an explicit annotation.
+        arg_type: Option<Type>,
         body: Box<Expr>,
     },
     App {
         callee: Box<Expr>,
-        type_params: Vec<Type>,
+        // The parser always yields `Some`, with an empty list for a brace-less
+        // call such as `f(x)`. `None` marks type arguments left for `tc` to
+        // reconstruct; `tc` also fills in an empty list when the callee is
+        // polymorphic.
+        type_params: Option<Vec<Type>>,
         arg: Box<Expr>,
     },
     Pair { left: Box<Expr>, right: Box<Expr> },
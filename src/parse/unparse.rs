@@ -0,0 +1,510 @@
+//! Rendering of the AST back to source text.
+//!
+//! The output is guaranteed to re-parse to an equal AST (see the round-trip
+//! proptest below). Two details make that non-trivial:
+//!
+//! * **Identifiers.** The lexer accepts unquoted names matching
+//!   `[A-Za-z_][A-Za-z0-9_]*`, backtick-quoted names for anything else (with `` ` ``
+//!   and `\` backslash-escaped), and an optional `#N` collision-id suffix. The
+//!   unparser quotes exactly when the unquoted rule would reject the name and
+//!   emits `#N` only when `collision_id != 0`.
+//!
+//! * **Precedence.** Parentheses are reintroduced only where the grammar needs
+//!   them — around a `Func` or `Pair` sitting in constructor position of an
+//!   `App`, or a `Pair` on the left of another `Pair`, and so on. Chained type
+//!   `App` nodes are folded back into the `foo(bar; baz)` form and chained `Pair`
+//!   nodes into `a, b, c`; an expression `App` is written one argument at a
+//!   time, as `f(a)(b)`.
+
+use std::fmt::{self, Write};
+
+use types::{Kind, Quantifier};
+use super::syntax::{Expr, ExprKind, Ident, Type, TypeKind, TypeParam};
+use expr::VarUsage;
+
+/// Render a type to its canonical source form.
+pub fn unparse_type(ty: &Type) -> String {
+    let mut out = String::new();
+    write_type(&mut out, ty, Prec::Top).unwrap();
+    out
+}
+
+/// Render an expression to its canonical source form.
+pub fn unparse_expr(expr: &Expr) -> String {
+    let mut out = String::new();
+    write_expr(&mut out, expr, Prec::Top).unwrap();
+    out
+}
+
+/// A `Display` adapter so callers can `println!("{}", unparse::Display(&expr))`.
+pub struct Display<'a, T: 'a>(pub &'a T);
+
+impl<'a> fmt::Display for Display<'a, Type> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_type(f, self.0, Prec::Top)
+    }
+}
+
+impl<'a> fmt::Display for Display<'a, Expr> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_expr(f, self.0, Prec::Top)
+    }
+}
+
+/// Precedence contexts, loosest to tightest. A child is parenthesized when the
+/// form it renders binds more loosely than the position requires.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Prec {
+    Top,
+    /// Inside a comma-separated pair.
+    Pair,
+    /// Right of an arrow, or the left component of a pair: anything but a pair.
+    Arrow,
+    /// Constructor position of an `App`, or left of an arrow.
+    App,
+    /// Anything that stands alone without parentheses.
+    Atom,
+}
+
+/// Emit an identifier, quoting and suffixing exactly as the lexer requires.
+fn write_ident(out: &mut dyn Write, ident: &Ident) -> fmt::Result {
+    if needs_quoting(&ident.name) {
+        out.write_char('`')?;
+        for ch in ident.name.chars() {
+            if ch == '`' || ch == '\\' {
+                out.write_char('\\')?;
+            }
+            out.write_char(ch)?;
+        }
+        out.write_char('`')?;
+    } else {
+        out.write_str(&ident.name)?;
+    }
+    if ident.collision_id != 0 {
+        write!(out, "#{}", ident.collision_id)?;
+    }
+    Ok(())
+}
+
+/// `true` if `name` cannot be written without backticks.
+fn needs_quoting(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        None => true, // the empty name must be quoted
+        Some(first) => {
+            if !(first.is_ascii_alphabetic() || first == '_') {
+                return true;
+            }
+            !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+    }
+}
+
+fn write_kind(out: &mut dyn Write, kind: &Kind) -> fmt::Result {
+    match *kind {
+        Kind::Type => out.write_char('*'),
+        Kind::Place => out.write_str("Place"),
+        Kind::Version => out.write_str("Version"),
+        Kind::Constructor { ref params, ref result } => {
+            out.write_char('(')?;
+            for (i, param) in params.iter().enumerate() {
+                if i != 0 {
+                    out.write_str("; ")?;
+                }
+                write_kind(out, param)?;
+            }
+            out.write_str(") -> ")?;
+            write_kind(out, result)
+        }
+    }
+}
+
+fn write_type_param(out: &mut dyn Write, param: &TypeParam) -> fmt::Result {
+    write_ident(out, &param.ident)?;
+    out.write_str(" : ")?;
+    write_kind(out, &param.kind)
+}
+
+/// Write `ty`, wrapping in parentheses if its form is looser than `ctx` demands.
+fn write_type(out: &mut dyn Write, ty: &Type, ctx: Prec) -> fmt::Result {
+    let prec = type_prec(&ty.node);
+    let paren = prec < ctx;
+    if paren {
+        out.write_char('(')?;
+    }
+    match ty.node {
+        TypeKind::Unit => out.write_str("()")?,
+        TypeKind::Var { ref ident } => write_ident(out, ident)?,
+        TypeKind::Quantified { quantifier, ref param, ref body } => {
+            out.write_str(match quantifier {
+                Quantifier::Exists => "exists ",
+                Quantifier::ForAll => "forall ",
+            })?;
+            out.write_char('{')?;
+            write_type_param(out, param)?;
+            out.write_str("} ")?;
+            write_type(out, body, Prec::Pair)?;
+        }
+        TypeKind::Func { ref params, ref arg, ref ret } => {
+            write_forall(out, params)?;
+            write_type(out, arg, Prec::App)?;
+            out.write_str(" -> ")?;
+            write_type(out, ret, Prec::Arrow)?;
+        }
+        TypeKind::Pair { ref left, ref right } => {
+            write_type(out, left, Prec::Arrow)?;
+            out.write_str(", ")?;
+            write_type(out, right, Prec::Pair)?;
+        }
+        TypeKind::App { .. } => write_type_app(out, ty)?,
+    }
+    if paren {
+        out.write_char(')')?;
+    }
+    Ok(())
+}
+
+/// Fold a left-nested `App` chain back into `head(a; b; c)` form.
+fn write_type_app(out: &mut dyn Write, ty: &Type) -> fmt::Result {
+    let mut params = Vec::new();
+    let mut head = ty;
+    while let TypeKind::App { ref constructor, ref param } = head.node {
+        params.push(param.as_ref());
+        head = constructor;
+    }
+    params.reverse();
+    write_type(out, head, Prec::App)?;
+    out.write_char('(')?;
+    for (i, param) in params.into_iter().enumerate() {
+        if i != 0 {
+            out.write_str("; ")?;
+        }
+        write_type(out, param, Prec::Top)?;
+    }
+    out.write_char(')')
+}
+
+fn write_forall(out: &mut dyn Write, params: &[TypeParam]) -> fmt::Result {
+    if params.is_empty() {
+        return Ok(());
+    }
+    out.write_str("forall {")?;
+    for (i, param) in params.iter().enumerate() {
+        if i != 0 {
+            out.write_str("; ")?;
+        }
+        write_type_param(out, param)?;
+    }
+    out.write_str("} ")
+}
+
+fn type_prec(ty: &TypeKind) -> Prec {
+    match *ty {
+        TypeKind::Unit | TypeKind::Var { .. } | TypeKind::App { .. } => Prec::Atom,
+        TypeKind::Func { .. } => Prec::Arrow,
+        TypeKind::Pair { .. } => Prec::Pair,
+        TypeKind::Quantified { .. } => Prec::Pair,
+    }
+}
+
+fn write_expr(out: &mut dyn Write, expr: &Expr, ctx: Prec) -> fmt::Result {
+    let prec = expr_prec(&expr.node);
+    let paren = prec < ctx;
+    if paren {
+        out.write_char('(')?;
+    }
+    match expr.node {
+        ExprKind::Unit => out.write_str("()")?,
+        ExprKind::Var { usage, ref ident } => {
+            if usage == VarUsage::Move {
+                out.write_str("move ")?;
+            }
+            write_ident(out, ident)?;
+        }
+        ExprKind::Func { ref type_params, ref arg_name, ref arg_type, ref body } => {
+            out.write_str("func ")?;
+            if !type_params.is_empty() {
+                out.write_char('{')?;
+                for (i, param) in type_params.iter().enumerate() {
+                    if i != 0 {
+                        out.write_str("; ")?;
+                    }
+                    write_type_param(out, param)?;
+                }
+                out.write_str("} ")?;
+            }
+            out.write_char('(')?;
+            write_ident(out, arg_name)?;
+            if let Some(ref ty) = *arg_type {
+                out.write_str(" : ")?;
+                write_type(out, ty, Prec::Top)?;
+            }
+            out.write_str(") -> ")?;
+            write_expr(out, body, Prec::Top)?;
+        }
+        ExprKind::App { ref callee, ref type_params, ref arg } => {
+            write_expr(out, callee, Prec::App)?;
+            if let Some(ref params) = *type_params {
+                if !params.is_empty() {
+                    out.write_char('{')?;
+                    for (i, param) in params.iter().enumerate() {
+                        if i != 0 {
+                            out.write_str("; ")?;
+                        }
+                        write_type(out, param, Prec::Top)?;
+                    }
+                    out.write_char('}')?;
+                }
+            }
+            out.write_char('(')?;
+            write_expr(out, arg, Prec::Top)?;
+            out.write_char(')')?;
+        }
+        ExprKind::Pair { ref left, ref right } => {
+            write_expr(out, left, Prec::Arrow)?;
+            out.write_str(", ")?;
+            write_expr(out, right, Prec::Pair)?;
+        }
+        ExprKind::Let { ref names, ref val, ref body } => {
+            out.write_str("let ")?;
+            write_idents(out, names)?;
+            out.write_str(" = ")?;
+            write_expr(out, val, Prec::Top)?;
+            out.write_str(" in ")?;
+            write_expr(out, body, Prec::Top)?;
+        }
+        ExprKind::LetExists { ref type_names, ref val_name, ref val, ref body } => {
+            out.write_str("let_exists {")?;
+            write_idents(out, type_names)?;
+            out.write_str("} ")?;
+            write_ident(out, val_name)?;
+            out.write_str(" = ")?;
+            write_expr(out, val, Prec::Top)?;
+            out.write_str(" in ")?;
+            write_expr(out, body, Prec::Top)?;
+        }
+        ExprKind::MakeExists { ref params, ref type_body, ref body } => {
+            out.write_str("make_exists {")?;
+            for (i, &(ref ident, ref ty)) in params.iter().enumerate() {
+                if i != 0 {
+                    out.write_str("; ")?;
+                }
+                write_ident(out, ident)?;
+                out.write_str(" = ")?;
+                write_type(out, ty, Prec::Top)?;
+            }
+            out.write_str("} ")?;
+            write_type(out, type_body, Prec::Top)?;
+            out.write_str(" of ")?;
+            write_expr(out, body, Prec::Top)?;
+        }
+    }
+    if paren {
+        out.write_char(')')?;
+    }
+    Ok(())
+}
+
+fn write_idents(out: &mut dyn Write, idents: &[Ident]) -> fmt::Result {
+    for (i, ident) in idents.iter().enumerate() {
+        if i != 0 {
+            out.write_str(", ")?;
+        }
+        write_ident(out, ident)?;
+    }
+    Ok(())
+}
+
+fn expr_prec(expr: &ExprKind) -> Prec {
+    match *expr {
+        ExprKind::Unit | ExprKind::App { .. } => Prec::Atom,
+        ExprKind::Var { usage, .. } => {
+            // `move x` must be parenthesized in constructor position.
+            if usage == VarUsage::Move { Prec::Arrow } else { Prec::Atom }
+        }
+        ExprKind::Pair { .. } => Prec::Pair,
+        ExprKind::Func { .. }
+        | ExprKind::Let { .. }
+        | ExprKind::LetExists { .. }
+        | ExprKind::MakeExists { .. } => Prec::Pair,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::{expr, type_};
+
+    fn type_round_trips(src: &str) {
+        let parsed = type_(src).expect("parse");
+        let printed = unparse_type(&parsed);
+        let reparsed = type_(&printed).expect("re-parse");
+        assert_eq!(parsed, reparsed, "printed as `{}`", printed);
+    }
+
+    fn expr_round_trips(src: &str) {
+        let parsed = expr(src).expect("parse");
+        let printed = unparse_expr(&parsed);
+        let reparsed = expr(&printed).expect("re-parse");
+        assert_eq!(parsed, reparsed, "printed as `{}`", printed);
+    }
+
+    #[test]
+    fn quotes_only_when_required() {
+        assert!(!needs_quoting("foo_bar0"));
+        assert!(needs_quoting("hello world"));
+        assert!(needs_quoting("42"));
+        assert!(needs_quoting(""));
+    }
+
+    #[test]
+    fn type_round_trip_examples() {
+        type_round_trips("foo(bar; baz)");
+        type_round_trips("foo -> bar -> baz");
+        type_round_trips("foo, bar, baz");
+        type_round_trips("exists {t : *} t");
+        type_round_trips("(foo -> bar)(baz)");
+    }
+
+    #[test]
+    fn expr_round_trip_examples() {
+        expr_round_trips("move `hello world`");
+        expr_round_trips("func (x : T) -> move x");
+        expr_round_trips("let x, y = move z in (x, move y)");
+        expr_round_trips("hello{T; U}(move world)");
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::{unparse_expr, unparse_type};
+    use super::super::{expr, type_};
+    use super::super::syntax::{Expr, ExprKind, Ident, Span, Spanned, Type, TypeKind};
+    use expr::VarUsage;
+
+    fn span() -> Span {
+        Span::new(0, 0)
+    }
+
+    // A small pool of well-formed unquoted names keeps the generated trees inside
+    // the grammar while still exercising the folding and precedence logic.
+    fn ident_strategy() -> impl Strategy<Value = Ident> {
+        (prop::sample::select(vec!["foo", "bar", "baz", "t", "u"]), 0u64..3)
+            .prop_map(|(name, collision_id)| Ident { name: name.to_owned(), collision_id })
+    }
+
+    fn type_strategy() -> impl Strategy<Value = Type> {
+        let leaf = prop_oneof![
+            Just(TypeKind::Unit),
+            ident_strategy().prop_map(|ident| TypeKind::Var { ident }),
+        ]
+        .prop_map(|node| Spanned::new(span(), node));
+
+        leaf.prop_recursive(4, 16, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(c, p)| Spanned::new(
+                    span(),
+                    TypeKind::App { constructor: Box::new(c), param: Box::new(p) },
+                )),
+                (inner.clone(), inner.clone()).prop_map(|(l, r)| Spanned::new(
+                    span(),
+                    TypeKind::Pair { left: Box::new(l), right: Box::new(r) },
+                )),
+                (inner.clone(), inner).prop_map(|(a, r)| Spanned::new(
+                    span(),
+                    TypeKind::Func { params: Vec::new(), arg: Box::new(a), ret: Box::new(r) },
+                )),
+            ]
+        })
+    }
+
+    fn expr_strategy() -> impl Strategy<Value = Expr> {
+        let usage = prop_oneof![Just(VarUsage::Copy), Just(VarUsage::Move)];
+        let leaf = prop_oneof![
+            Just(ExprKind::Unit),
+            (usage, ident_strategy()).prop_map(|(usage, ident)| ExprKind::Var { usage, ident }),
+        ]
+        .prop_map(|node| Spanned::new(span(), node));
+
+        leaf.prop_recursive(5, 48, 2, |inner| {
+            // An optional argument annotation and a right-nested list of binder
+            // names, reused by the binding forms below.
+            let arg_type = prop_oneof![Just(None), type_strategy().prop_map(Some)];
+            let names = prop::collection::vec(ident_strategy(), 1..3);
+            prop_oneof![
+                // Mirror the parser, which represents a brace-less application as
+                // `Some(vec![])` (see `test_expr`) rather than `None`. A `None`
+                // here would unparse identically to `Some(vec![])` and break the
+                // structural round-trip equality.
+                (inner.clone(), inner.clone()).prop_map(|(c, a)| Spanned::new(
+                    span(),
+                    ExprKind::App {
+                        callee: Box::new(c),
+                        type_params: Some(Vec::new()),
+                        arg: Box::new(a),
+                    },
+                )),
+                (inner.clone(), inner.clone()).prop_map(|(l, r)| Spanned::new(
+                    span(),
+                    ExprKind::Pair { left: Box::new(l), right: Box::new(r) },
+                )),
+                // `func` — exercises the `Pair`-bodied case whose body is emitted at
+                // `Prec::Top`.
+                (ident_strategy(), arg_type, inner.clone()).prop_map(|(arg_name, arg_type, body)| {
+                    Spanned::new(
+                        span(),
+                        ExprKind::Func {
+                            type_params: Vec::new(),
+                            arg_name,
+                            arg_type,
+                            body: Box::new(body),
+                        },
+                    )
+                }),
+                (names.clone(), inner.clone(), inner.clone()).prop_map(|(names, val, body)| {
+                    Spanned::new(
+                        span(),
+                        ExprKind::Let { names, val: Box::new(val), body: Box::new(body) },
+                    )
+                }),
+                (names, ident_strategy(), inner.clone(), inner.clone()).prop_map(
+                    |(type_names, val_name, val, body)| Spanned::new(
+                        span(),
+                        ExprKind::LetExists {
+                            type_names,
+                            val_name,
+                            val: Box::new(val),
+                            body: Box::new(body),
+                        },
+                    )
+                ),
+                (
+                    prop::collection::vec((ident_strategy(), type_strategy()), 1..3),
+                    type_strategy(),
+                    inner,
+                ).prop_map(|(params, type_body, body)| Spanned::new(
+                    span(),
+                    ExprKind::MakeExists { params, type_body, body: Box::new(body) },
+                )),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn type_parse_unparse_parse(ty in type_strategy()) {
+            let printed = unparse_type(&ty);
+            let reparsed = type_(&printed).expect("re-parse");
+            prop_assert_eq!(ty, reparsed);
+        }
+
+        #[test]
+        fn expr_parse_unparse_parse(e in expr_strategy()) {
+            let printed = unparse_expr(&e);
+            let reparsed = expr(&printed).expect("re-parse");
+            prop_assert_eq!(e, reparsed);
+        }
+    }
+}